@@ -0,0 +1,215 @@
+//! Protoc discovery, a one-call `compile()` helper, and `FileDescriptorSet` generation
+//! for reflection.
+//!
+//! Gated behind the `protoc` feature, since it pulls in `prost-build` and shells out to
+//! an external `protoc` binary rather than just embedding and extracting bytes.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{anyhow, Result};
+
+use crate::ExtractedProtoDir;
+
+/// The minimum `protoc` version this crate has been tested against.
+const MIN_PROTOC_VERSION: (u64, u64, u64) = (3, 0, 0);
+
+/// The reason [`locate_protoc`] failed.
+#[derive(Debug)]
+pub enum ProtocError {
+    /// No `protoc` binary could be found via the `PROTOC` environment variable or `PATH`.
+    NotFound,
+    /// `protoc` was found, but its reported version is older than [`MIN_PROTOC_VERSION`].
+    TooOld {
+        found: (u64, u64, u64),
+        minimum: (u64, u64, u64),
+    },
+    /// `protoc --version` printed something this crate couldn't parse.
+    UnparseableVersion(String),
+}
+
+impl fmt::Display for ProtocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtocError::NotFound => write!(
+                f,
+                "could not find a `protoc` binary; set the PROTOC environment variable or install protoc on PATH"
+            ),
+            ProtocError::TooOld { found, minimum } => write!(
+                f,
+                "protoc {}.{}.{} is older than the minimum required {}.{}.{}",
+                found.0, found.1, found.2, minimum.0, minimum.1, minimum.2
+            ),
+            ProtocError::UnparseableVersion(raw) => {
+                write!(f, "could not parse a protoc version from `{raw}`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProtocError {}
+
+/// Locates a usable `protoc` binary.
+///
+/// Honors the `PROTOC` environment variable if set, otherwise falls back to `protoc` on
+/// `PATH`. Either way, the binary's reported `--version` is checked against
+/// [`MIN_PROTOC_VERSION`] and rejected if it's older, so callers get a clear error
+/// instead of a confusing downstream compile failure.
+///
+/// # Errors
+///
+/// Returns [`ProtocError::NotFound`] if no binary could be run, [`ProtocError::TooOld`]
+/// if its version is below the minimum, or [`ProtocError::UnparseableVersion`] if the
+/// version output wasn't in the expected `"libprotoc X.Y.Z"` form.
+pub fn locate_protoc() -> Result<PathBuf, ProtocError> {
+    let protoc = std::env::var_os("PROTOC")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("protoc"));
+
+    let output = Command::new(&protoc)
+        .arg("--version")
+        .output()
+        .map_err(|_| ProtocError::NotFound)?;
+    if !output.status.success() {
+        return Err(ProtocError::NotFound);
+    }
+
+    let version_str = String::from_utf8_lossy(&output.stdout);
+    let version = parse_protoc_version(&version_str)
+        .ok_or_else(|| ProtocError::UnparseableVersion(version_str.trim().to_string()))?;
+
+    if version < MIN_PROTOC_VERSION {
+        return Err(ProtocError::TooOld {
+            found: version,
+            minimum: MIN_PROTOC_VERSION,
+        });
+    }
+
+    Ok(protoc)
+}
+
+/// Parses a `"libprotoc X.Y.Z"` version string into `(major, minor, patch)`.
+fn parse_protoc_version(raw: &str) -> Option<(u64, u64, u64)> {
+    let version = raw.trim().strip_prefix("libprotoc ")?;
+    let mut parts = version.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().unwrap_or("0").parse().ok()?;
+    let patch = parts.next().unwrap_or("0").parse().ok()?;
+    Some((major, minor, patch))
+}
+
+/// Builds on an [`ExtractedProtoDir`] to drive `prost-build` without every `build.rs`
+/// having to hand-assemble the include paths itself: `PROTO_DIR.extract(out)?.compiler().compile()?`.
+pub struct ProtoCompiler<'a> {
+    extracted: &'a ExtractedProtoDir,
+    config: prost_build::Config,
+}
+
+impl<'a> ProtoCompiler<'a> {
+    fn new(extracted: &'a ExtractedProtoDir) -> Self {
+        ProtoCompiler {
+            extracted,
+            config: prost_build::Config::new(),
+        }
+    }
+
+    /// Gives direct access to the underlying `prost_build::Config`, for settings this
+    /// builder doesn't expose its own method for.
+    pub fn config_mut(&mut self) -> &mut prost_build::Config {
+        &mut self.config
+    }
+
+    /// Locates and validates `protoc` (see [`locate_protoc`]), then compiles this
+    /// directory's protos against its include paths.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `protoc` can't be located or is too old, or if compilation
+    /// itself fails.
+    pub fn compile(&mut self) -> Result<()> {
+        let protoc = locate_protoc().map_err(|e| anyhow!(e.to_string()))?;
+        std::env::set_var("PROTOC", protoc);
+
+        self.config
+            .compile_protos(self.extracted.protos(), self.extracted.include_paths())?;
+        Ok(())
+    }
+}
+
+impl ExtractedProtoDir {
+    /// Returns a [`ProtoCompiler`] preloaded with this directory's protos and include
+    /// paths.
+    pub fn compiler(&self) -> ProtoCompiler<'_> {
+        ProtoCompiler::new(self)
+    }
+
+    /// Compiles this directory's protos into a single encoded `FileDescriptorSet` and
+    /// writes it to `path`, for embedding with `include_bytes!` and serving via e.g.
+    /// `tonic_reflection`.
+    ///
+    /// Imported dependencies are included (`protoc --include_imports`), so the set is
+    /// self-contained.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `protoc` can't be located or is too old, or if it exits
+    /// unsuccessfully.
+    pub fn descriptor_set(&self, path: &Path) -> Result<()> {
+        let status = self
+            .descriptor_set_command(format!("--descriptor_set_out={}", path.display()))?
+            .status()?;
+        if !status.success() {
+            return Err(anyhow!("protoc exited with {status}"));
+        }
+        Ok(())
+    }
+
+    /// Same as [`Self::descriptor_set`], but returns the encoded bytes directly instead
+    /// of writing them to a caller-chosen path, by having `protoc` write the set to
+    /// stdout (`--descriptor_set_out=-`) rather than a temporary file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error under the same conditions as [`Self::descriptor_set`].
+    pub fn descriptor_set_bytes(&self) -> Result<Vec<u8>> {
+        let output = self
+            .descriptor_set_command("--descriptor_set_out=-")?
+            .stdout(std::process::Stdio::piped())
+            .output()?;
+        if !output.status.success() {
+            return Err(anyhow!("protoc exited with {}", output.status));
+        }
+        Ok(output.stdout)
+    }
+
+    /// Builds the shared `protoc --include_imports <descriptor_set_out_arg> -I... protos...`
+    /// invocation behind [`Self::descriptor_set`] and [`Self::descriptor_set_bytes`].
+    fn descriptor_set_command(&self, descriptor_set_out_arg: impl AsRef<str>) -> Result<Command> {
+        let protoc = locate_protoc().map_err(|e| anyhow!(e.to_string()))?;
+
+        let mut cmd = Command::new(protoc);
+        cmd.arg("--include_imports")
+            .arg(descriptor_set_out_arg.as_ref());
+        for include_path in self.include_paths() {
+            cmd.arg(format!("-I{}", include_path.display()));
+        }
+        for proto in self.protos() {
+            cmd.arg(proto);
+        }
+
+        Ok(cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_version_string() {
+        assert_eq!(parse_protoc_version("libprotoc 3.21.12"), Some((3, 21, 12)));
+        assert_eq!(parse_protoc_version("libprotoc 25.1"), Some((25, 1, 0)));
+        assert_eq!(parse_protoc_version("not protoc"), None);
+    }
+}
@@ -0,0 +1,334 @@
+//! Import-resolution for extracted Protobuf trees.
+//!
+//! `protoc` only reports a missing import once it starts compiling, and the message
+//! it gives points at line/column in the importing file rather than which include
+//! roots were actually searched. [`ExtractedProtoDir::validate`] catches the same
+//! problem earlier and with a more useful error.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::ExtractedProtoDir;
+
+/// The reason [`ExtractedProtoDir::validate`] failed.
+#[derive(Debug)]
+pub enum ImportError {
+    /// A file's `import "...";` statement did not resolve under any include root.
+    MissingImport {
+        /// The `.proto` file containing the unresolved `import`.
+        file: PathBuf,
+        /// The import path as written in the `import "...";` statement.
+        import: String,
+        /// The include roots that were searched for `import`.
+        searched_roots: Vec<PathBuf>,
+    },
+    /// A `.proto` file resolved to a path on disk, but couldn't be read.
+    Io {
+        /// The file that failed to read.
+        file: PathBuf,
+        /// The underlying I/O error.
+        source: std::io::Error,
+    },
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::MissingImport {
+                file,
+                import,
+                searched_roots,
+            } => write!(
+                f,
+                "{} imports \"{}\", which could not be found in any of: {}",
+                file.display(),
+                import,
+                searched_roots
+                    .iter()
+                    .map(|root| root.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+            ImportError::Io { file, source } => {
+                write!(f, "failed to read {}: {source}", file.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ImportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ImportError::MissingImport { .. } => None,
+            ImportError::Io { source, .. } => Some(source),
+        }
+    }
+}
+
+/// The import dependency graph of an extracted Protobuf tree, as built by
+/// [`ExtractedProtoDir::validate`].
+///
+/// Keyed by each scanned file's path (relative to its include root), holding the raw
+/// import strings it declares.
+#[derive(Debug, Default)]
+pub struct ImportGraph {
+    edges: HashMap<PathBuf, Vec<String>>,
+}
+
+impl ImportGraph {
+    /// Returns the import paths declared by `file`, or an empty slice if `file` was
+    /// not part of the scanned tree.
+    pub fn imports_of(&self, file: &Path) -> &[String] {
+        self.edges.get(file).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Returns the scanned files in a deterministic, dependency-first compile order:
+    /// a file only appears after the files it imports.
+    ///
+    /// Proto import cycles are legal (unlike most module systems), so a cycle is
+    /// simply broken at an arbitrary edge rather than rejected.
+    pub fn compile_order(&self) -> Vec<PathBuf> {
+        let mut order = Vec::with_capacity(self.edges.len());
+        let mut visited = HashSet::new();
+        let mut in_progress = HashSet::new();
+
+        let mut files: Vec<&PathBuf> = self.edges.keys().collect();
+        files.sort();
+        for file in files {
+            self.visit(file, &mut visited, &mut in_progress, &mut order);
+        }
+        order
+    }
+
+    fn visit(
+        &self,
+        file: &Path,
+        visited: &mut HashSet<PathBuf>,
+        in_progress: &mut HashSet<PathBuf>,
+        order: &mut Vec<PathBuf>,
+    ) {
+        if visited.contains(file) || in_progress.contains(file) {
+            return;
+        }
+        in_progress.insert(file.to_path_buf());
+        if let Some(imports) = self.edges.get(file) {
+            for import in imports {
+                let import_path = PathBuf::from(import);
+                if self.edges.contains_key(&import_path) {
+                    self.visit(&import_path, visited, in_progress, order);
+                }
+            }
+        }
+        in_progress.remove(file);
+        visited.insert(file.to_path_buf());
+        order.push(file.to_path_buf());
+    }
+}
+
+impl ExtractedProtoDir {
+    /// Scans every file in [`Self::protos`] for `import "path";` statements and checks
+    /// that each one resolves to a file under one of [`Self::include_paths`]. For a
+    /// directory extracted via [`MergedProtoDir::extract_all`](crate::MergedProtoDir::extract_all)
+    /// that is the primary tree only; the files of the merged-in trees are not scanned.
+    ///
+    /// This is a single-pass line scanner: it skips `//` line comments and `/* */`
+    /// block comments and looks for lines matching
+    /// `^\s*import\s+(public\s+|weak\s+)?"([^"]+)"\s*;`. It does not otherwise parse
+    /// the `.proto` grammar (e.g. `syntax`/`package` ordering is not checked).
+    ///
+    /// A file that imports one of Google's well-known types (or anything else outside
+    /// its own tree) only validates if that import's source has also been extracted
+    /// into one of [`Self::include_paths`] — e.g. by [`ProtoDir::merge`](crate::ProtoDir::merge)ing
+    /// it in before calling [`ProtoDir::extract`](crate::ProtoDir::extract) /
+    /// [`MergedProtoDir::extract_all`](crate::MergedProtoDir::extract_all). Otherwise
+    /// this reports the import as missing just as `protoc` would.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ImportError::MissingImport`] naming the importing file, the missing
+    /// import path, and the roots that were searched — before `protoc` gets a chance
+    /// to fail on it with a less specific message. Returns [`ImportError::Io`] if a
+    /// file that resolved to an on-disk path couldn't be read.
+    pub fn validate(&self) -> Result<ImportGraph, ImportError> {
+        let mut edges = HashMap::new();
+
+        for file in self.protos() {
+            let Some(disk_path) = self.resolve_disk_path(file) else {
+                continue;
+            };
+            let contents =
+                std::fs::read_to_string(&disk_path).map_err(|source| ImportError::Io {
+                    file: disk_path.clone(),
+                    source,
+                })?;
+            edges.insert(file.clone(), parse_imports(&contents));
+        }
+
+        for (file, imports) in &edges {
+            for import in imports {
+                let found = self
+                    .include_paths()
+                    .iter()
+                    .any(|root| root.join(import).exists());
+                if !found {
+                    return Err(ImportError::MissingImport {
+                        file: file.clone(),
+                        import: import.clone(),
+                        searched_roots: self.include_paths().to_vec(),
+                    });
+                }
+            }
+        }
+
+        Ok(ImportGraph { edges })
+    }
+
+    fn resolve_disk_path(&self, file: &Path) -> Option<PathBuf> {
+        self.include_paths()
+            .iter()
+            .map(|root| root.join(file))
+            .find(|candidate| candidate.exists())
+    }
+}
+
+/// Scans `contents` line by line for `import "path";` statements, skipping `//` and
+/// `/* */` comments wherever they fall on a line, including ones that sit inline
+/// before, inside, or after the `import` keyword itself.
+fn parse_imports(contents: &str) -> Vec<String> {
+    let mut imports = Vec::new();
+    let mut in_block_comment = false;
+
+    for raw_line in contents.lines() {
+        let stripped = strip_comments(raw_line, &mut in_block_comment);
+        let line = stripped.trim();
+        if let Some(import) = parse_import_line(line) {
+            imports.push(import);
+        }
+    }
+
+    imports
+}
+
+/// Removes comments from `line`: a `// ...` line comment (which runs to the end of the
+/// line, so nothing after it — including a stray `/*` — can open a block comment) and
+/// any `/* ... */` spans, updating `in_block_comment` to track a span left open at the
+/// end of the line (and honored at the start of the next one).
+fn strip_comments(line: &str, in_block_comment: &mut bool) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut rest = line;
+
+    loop {
+        if *in_block_comment {
+            match rest.find("*/") {
+                Some(end) => {
+                    rest = &rest[end + 2..];
+                    *in_block_comment = false;
+                }
+                None => return result,
+            }
+            continue;
+        }
+
+        let line_comment = rest.find("//");
+        let block_comment = rest.find("/*");
+        let line_comment_comes_first = match (line_comment, block_comment) {
+            (Some(lc), Some(bc)) => lc < bc,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+
+        if line_comment_comes_first {
+            result.push_str(&rest[..line_comment.unwrap()]);
+            return result;
+        }
+        match block_comment {
+            Some(start) => {
+                result.push_str(&rest[..start]);
+                rest = &rest[start + 2..];
+                *in_block_comment = true;
+            }
+            None => {
+                result.push_str(rest);
+                return result;
+            }
+        }
+    }
+}
+
+/// Matches `^\s*import\s+(public\s+|weak\s+)?"([^"]+)"\s*;` against an already-trimmed,
+/// comment-free line.
+fn parse_import_line(line: &str) -> Option<String> {
+    let rest = line.strip_prefix("import")?;
+    if !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let rest = rest.trim_start();
+    let rest = rest
+        .strip_prefix("public")
+        .or_else(|| rest.strip_prefix("weak"))
+        .map(str::trim_start)
+        .unwrap_or(rest);
+    let rest = rest.strip_prefix('"')?;
+    let end = rest.find('"')?;
+    let import = &rest[..end];
+    let tail = rest[end + 1..].trim_start();
+    tail.starts_with(';').then(|| import.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_plain_import() {
+        assert_eq!(
+            parse_imports("import \"foo/v1/foo.proto\";"),
+            vec!["foo/v1/foo.proto".to_string()],
+        );
+    }
+
+    #[test]
+    fn parses_public_and_weak_imports() {
+        assert_eq!(
+            parse_imports(
+                "import public \"a.proto\";\nimport weak \"b.proto\";\nimport \"c.proto\";"
+            ),
+            vec![
+                "a.proto".to_string(),
+                "b.proto".to_string(),
+                "c.proto".to_string()
+            ],
+        );
+    }
+
+    #[test]
+    fn skips_line_and_block_comments() {
+        let contents = "// import \"commented.proto\";\n/* import \"also_commented.proto\";\n*/\nimport \"real.proto\";";
+        assert_eq!(parse_imports(contents), vec!["real.proto".to_string()]);
+    }
+
+    #[test]
+    fn strips_inline_block_comment_sharing_the_import_line() {
+        assert_eq!(
+            parse_imports("import /* inline */ \"real.proto\"; // trailing"),
+            vec!["real.proto".to_string()],
+        );
+        assert_eq!(
+            parse_imports("/* leading */ import \"real.proto\";"),
+            vec!["real.proto".to_string()],
+        );
+    }
+
+    #[test]
+    fn line_comment_containing_block_comment_marker_does_not_swallow_later_imports() {
+        // The "/*" inside this line comment must not open a block comment that then
+        // eats every subsequent line.
+        let contents =
+            "// see foo/*.proto for the schema\nimport \"a.proto\";\nimport \"b.proto\";";
+        assert_eq!(
+            parse_imports(contents),
+            vec!["a.proto".to_string(), "b.proto".to_string()],
+        );
+    }
+}
@@ -26,7 +26,9 @@
 //! // pub const PROTO_DIR: include_proto_dir::ProtoDir = include_proto_dir!("$CARGO_MANIFEST_DIR/proto");
 //! ```
 //!
-//! In your build script `build.rs`, you can extract the embedded `.proto` files and generate Rust code using e.g. `prost-build`:
+//! In your build script `build.rs`, you can extract the embedded `.proto` files and generate Rust
+//! code using e.g. `prost-build`. With the `protoc` feature enabled, [`ExtractedProtoDir::compiler`]
+//! wires this up for you:
 //!
 //! ```rust,ignore
 //! mod some_proto_crate {
@@ -36,15 +38,15 @@
 //!
 //! use some_proto_crate::PROTO_DIR;
 //! use std::path::PathBuf;
-//! extern crate build_deps;
 //!
 //! fn main() -> Result<(), Box<dyn std::error::Error>> {
 //!     let out_dir = PathBuf::from(std::env::var("OUT_DIR")?);
 //!     let proto_dir = PROTO_DIR.extract(&out_dir)?;
 //!
-//!     build_deps::rerun_if_changed_paths(&proto_dir.to_glob()).map_err(|e| format!("{:?}", e))?;
-//!     let mut builder = prost_build::Config::new();
-//!     builder.compile_protos(proto_dir.protos(), &[proto_dir.as_path()])?;
+//!     for path in proto_dir.source_rerun_paths() {
+//!         println!("cargo:rerun-if-changed={}", path.display());
+//!     }
+//!     proto_dir.compiler().compile()?;
 //!
 //!     Ok(())
 //! }
@@ -55,6 +57,17 @@ use include_dir::Dir;
 
 use std::path::{Path, PathBuf};
 
+mod import_graph;
+pub use import_graph::{ImportError, ImportGraph};
+
+#[cfg(feature = "protoc")]
+mod compile;
+#[cfg(feature = "protoc")]
+pub use compile::{locate_protoc, ProtoCompiler, ProtocError};
+
+#[cfg(feature = "protoc")]
+mod module_tree;
+
 /// A struct that represents a directory of embedded Protobuf files.
 ///
 /// The `ProtoDir` struct allows you to extract the embedded `.proto` files to a specified directory,
@@ -62,9 +75,33 @@ use std::path::{Path, PathBuf};
 pub struct ProtoDir<'a> {
     /// The embedded directory containing the Protobuf files.
     pub dir: Dir<'a>,
+    /// The manifest directory of the crate that invoked [`include_proto_dir!`], captured
+    /// via `env!("CARGO_MANIFEST_DIR")` at the macro's expansion site.
+    #[doc(hidden)]
+    pub manifest_dir: &'static str,
+    /// The path literal passed to [`include_proto_dir!`], before `$CARGO_MANIFEST_DIR`
+    /// substitution.
+    #[doc(hidden)]
+    pub path_literal: &'static str,
 }
 
 impl<'a> ProtoDir<'a> {
+    /// Returns the absolute, on-disk directory the embedded `.proto` files were
+    /// originally read from.
+    ///
+    /// This resolves the `$CARGO_MANIFEST_DIR` placeholder in the path given to
+    /// [`include_proto_dir!`] using the manifest directory captured when the macro was
+    /// expanded, so it is correct even when `PROTO_DIR` is used from a downstream
+    /// crate.
+    pub fn source_dir(&self) -> PathBuf {
+        // A relative literal is resolved against the manifest directory, as
+        // `include_dir!` does; an absolute one replaces it in `join`.
+        PathBuf::from(self.manifest_dir).join(
+            self.path_literal
+                .replace("$CARGO_MANIFEST_DIR", self.manifest_dir),
+        )
+    }
+
     /// Extracts the embedded Protobuf files into the specified output directory, adding a "proto" folder as the parent.
     ///
     /// This function extracts all the embedded `.proto` files into a `proto` subdirectory within the provided `out_dir`.
@@ -100,15 +137,51 @@ impl<'a> ProtoDir<'a> {
     pub fn extract(&self, out_dir: &Path) -> Result<ExtractedProtoDir> {
         let proto_path = out_dir.join("proto");
         self.dir.extract(&proto_path)?;
+        let source_dir = self.source_dir();
+        let files: Vec<PathBuf> = self
+            .dir
+            .find("**/*.proto")?
+            .map(|f| f.path().to_path_buf())
+            .collect();
         Ok(ExtractedProtoDir {
-            path: proto_path,
-            files: self
-                .dir
-                .find("**/*.proto")?
-                .map(|f| f.path().to_path_buf())
-                .collect::<Vec<_>>(),
+            path: proto_path.clone(),
+            rerun_sources: files.iter().map(|f| source_dir.join(f)).collect(),
+            files,
+            include_paths: vec![proto_path],
         })
     }
+
+    /// Combines this directory with one or more other embedded Protobuf directories so
+    /// their trees can be extracted together.
+    ///
+    /// This is useful when a crate's `.proto` files `import` types from other proto
+    /// crates (Google's well-known types, a shared base package, etc.): each imported
+    /// crate typically ships its own `ProtoDir`, and `protoc` needs every one of those
+    /// trees on its include path to resolve the imports. `merge` just groups the
+    /// directories; call [`MergedProtoDir::extract_all`] to write them to disk.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use include_proto_dir::include_proto_dir;
+    ///
+    /// const PROTO_DIR: include_proto_dir::ProtoDir = include_proto_dir!("$CARGO_MANIFEST_DIR/proto");
+    /// const OTHER_PROTO_DIR: include_proto_dir::ProtoDir = include_proto_dir!("$CARGO_MANIFEST_DIR/proto");
+    ///
+    /// let merged = PROTO_DIR.merge(&[&OTHER_PROTO_DIR]);
+    /// ```
+    pub fn merge(&self, others: &[&ProtoDir<'a>]) -> MergedProtoDir<'a> {
+        let mut entries = Vec::with_capacity(1 + others.len());
+        entries.push(MergedEntry {
+            dir: self.dir,
+            source_dir: self.source_dir(),
+        });
+        entries.extend(others.iter().map(|other| MergedEntry {
+            dir: other.dir,
+            source_dir: other.source_dir(),
+        }));
+        MergedProtoDir { entries }
+    }
 }
 
 impl<'a> AsRef<Dir<'a>> for ProtoDir<'a> {
@@ -117,6 +190,67 @@ impl<'a> AsRef<Dir<'a>> for ProtoDir<'a> {
     }
 }
 
+struct MergedEntry<'a> {
+    dir: Dir<'a>,
+    source_dir: PathBuf,
+}
+
+/// A view over several embedded Protobuf directories, produced by [`ProtoDir::merge`].
+///
+/// Unlike a single `ProtoDir`, extracting a `MergedProtoDir` writes each constituent
+/// tree into its own subdirectory of the output root and exposes all of them as include
+/// roots, so cross-crate `import "...";` statements resolve.
+pub struct MergedProtoDir<'a> {
+    entries: Vec<MergedEntry<'a>>,
+}
+
+impl<'a> MergedProtoDir<'a> {
+    /// Extracts every constituent directory into its own subdirectory of `out_dir` and
+    /// returns an `ExtractedProtoDir` whose include roots cover the union of all of
+    /// them.
+    ///
+    /// The first directory passed to [`ProtoDir::merge`] is treated as the primary one:
+    /// `ExtractedProtoDir::as_path` and `ExtractedProtoDir::protos` refer to it, while
+    /// `ExtractedProtoDir::include_paths` exposes every root so
+    /// `prost_build::Config::compile_protos` can see the full import closure.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if extracting any of the constituent directories fails.
+    pub fn extract_all(&self, out_dir: &Path) -> Result<ExtractedProtoDir> {
+        let mut include_paths = Vec::with_capacity(self.entries.len());
+        let mut rerun_sources = Vec::new();
+        let mut files = Vec::new();
+
+        for (index, entry) in self.entries.iter().enumerate() {
+            let sub_path = out_dir.join(format!("proto_{index}"));
+            entry.dir.extract(&sub_path)?;
+
+            let entry_files: Vec<PathBuf> = entry
+                .dir
+                .find("**/*.proto")?
+                .map(|f| f.path().to_path_buf())
+                .collect();
+            // `protos()`/`as_path()` only ever refer to the primary (first-merged)
+            // directory, but every constituent's sources should still retrigger the
+            // build when edited.
+            rerun_sources.extend(entry_files.iter().map(|f| entry.source_dir.join(f)));
+            if index == 0 {
+                files.extend(entry_files);
+            }
+
+            include_paths.push(sub_path);
+        }
+
+        Ok(ExtractedProtoDir {
+            path: include_paths[0].clone(),
+            files,
+            include_paths,
+            rerun_sources,
+        })
+    }
+}
+
 /// A struct that represents the extracted Protobuf directory.
 ///
 /// After extracting the embedded `.proto` files using `ProtoDir`, an `ExtractedProtoDir` instance
@@ -147,13 +281,43 @@ impl<'a> AsRef<Dir<'a>> for ProtoDir<'a> {
 pub struct ExtractedProtoDir {
     path: PathBuf,
     files: Vec<PathBuf>,
+    include_paths: Vec<PathBuf>,
+    rerun_sources: Vec<PathBuf>,
 }
 
 impl ExtractedProtoDir {
+    /// Returns every include root the extracted protos should be compiled against.
+    ///
+    /// For a directory extracted via [`ProtoDir::extract`] this is just `[as_path()]`.
+    /// For one extracted via [`MergedProtoDir::extract_all`] it covers every
+    /// constituent directory, so `prost_build::Config::compile_protos(protos, &include_paths)`
+    /// sees the full import closure.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use include_proto_dir::include_proto_dir;
+    /// const PROTO_DIR: include_proto_dir::ProtoDir = include_proto_dir!("$CARGO_MANIFEST_DIR/proto");
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let tmpdir = tempfile::tempdir()?;
+    ///     let extracted_proto_dir = PROTO_DIR.extract(tmpdir.path())?;
+    ///     let include_paths = extracted_proto_dir.include_paths();
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn include_paths(&self) -> &[PathBuf] {
+        &self.include_paths
+    }
+
     /// Returns the glob pattern for use with `rerun-if-changed` directives in build scripts.
     ///
     /// This method generates a glob pattern that matches all files within the extracted Protobuf directory.
-    /// It is useful for invalidating the build when any of the `.proto` files change.
+    ///
+    /// Note that this points at the extraction directory under `OUT_DIR`, which is rewritten on
+    /// every build-script run regardless of whether the embedded `.proto` sources actually
+    /// changed, so a `rerun-if-changed` built from it never fires on a real edit. Prefer
+    /// [`Self::source_rerun_paths`], which points at the original on-disk `.proto` files.
     ///
     /// # Examples
     ///
@@ -172,6 +336,42 @@ impl ExtractedProtoDir {
         format!("{}/**", self.path.display())
     }
 
+    /// Returns the original, on-disk `.proto` files the embedded bytes were extracted
+    /// from, for use in `cargo:rerun-if-changed` directives.
+    ///
+    /// Unlike [`Self::to_glob`], which points at the extraction directory under
+    /// `OUT_DIR` (rewritten on every build-script run whether or not the sources
+    /// changed), this resolves each embedded file against the source directory(ies)
+    /// captured by [`include_proto_dir!`] at macro-expansion time, so incremental
+    /// rebuilds actually trigger on real edits. For a directory extracted via
+    /// [`MergedProtoDir::extract_all`] this covers every constituent directory, not
+    /// just the primary one, so edits to a merged-in dependency's sources also
+    /// retrigger the build. Files whose original source can no longer be found (e.g.
+    /// the crate was packaged without its `proto` directory) are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use include_proto_dir::include_proto_dir;
+    /// const PROTO_DIR: include_proto_dir::ProtoDir = include_proto_dir!("$CARGO_MANIFEST_DIR/proto");
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let tmpdir = tempfile::tempdir()?;
+    ///     let extracted_proto_dir = PROTO_DIR.extract(tmpdir.path())?;
+    ///     for path in extracted_proto_dir.source_rerun_paths() {
+    ///         println!("cargo:rerun-if-changed={}", path.display());
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn source_rerun_paths(&self) -> Vec<PathBuf> {
+        self.rerun_sources
+            .iter()
+            .filter(|path| path.exists())
+            .cloned()
+            .collect()
+    }
+
     /// Returns the path to the extracted Protobuf directory.
     ///
     /// # Examples
@@ -236,6 +436,8 @@ macro_rules! include_proto_dir {
     ($path:tt) => {
         $crate::ProtoDir {
             dir: include_dir::include_dir!($path),
+            manifest_dir: env!("CARGO_MANIFEST_DIR"),
+            path_literal: $path,
         }
     };
 }
@@ -270,6 +472,8 @@ mod tests {
         // Assuming there's a proto file at "./proto/v1/foo/foo.proto" for testing
         ProtoDir {
             dir: include_dir::include_dir!("proto"),
+            manifest_dir: env!("CARGO_MANIFEST_DIR"),
+            path_literal: "proto",
         }
     }
 
@@ -332,6 +536,75 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_merge_extract_all() -> Result<()> {
+        let proto_dir = create_mock_proto_dir();
+        let other_proto_dir = create_mock_proto_dir();
+        let tmp_dir = tempdir()?;
+
+        let merged = proto_dir.merge(&[&other_proto_dir]);
+        let extracted_proto_dir = merged.extract_all(tmp_dir.path())?;
+
+        // The primary directory's files and path are exposed as before...
+        assert!(!extracted_proto_dir.protos().is_empty());
+        assert!(extracted_proto_dir.as_path().exists());
+
+        // ...but every constituent root is available for import resolution.
+        let include_paths = extracted_proto_dir.include_paths();
+        assert_eq!(include_paths.len(), 2);
+        for path in include_paths {
+            assert!(path.join("foo/v1/foo.proto").exists());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate() -> Result<()> {
+        let proto_dir = create_mock_proto_dir();
+        let tmp_dir = tempdir()?;
+        let extracted_proto_dir = proto_dir.extract(tmp_dir.path())?;
+
+        // A self-contained tree with no unresolved imports validates cleanly.
+        extracted_proto_dir.validate()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_source_rerun_paths() -> Result<()> {
+        let proto_dir = create_mock_proto_dir();
+        let tmp_dir = tempdir()?;
+        let extracted_proto_dir = proto_dir.extract(tmp_dir.path())?;
+
+        let rerun_paths = extracted_proto_dir.source_rerun_paths();
+        assert_eq!(rerun_paths.len(), extracted_proto_dir.protos().len());
+        for path in &rerun_paths {
+            // Unlike `to_glob()`, these point at the real source tree, not OUT_DIR.
+            assert!(!path.starts_with(tmp_dir.path()));
+            assert!(path.exists());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merged_source_rerun_paths_cover_every_entry() -> Result<()> {
+        let proto_dir = create_mock_proto_dir();
+        let other_proto_dir = create_mock_proto_dir();
+        let tmp_dir = tempdir()?;
+
+        let merged = proto_dir.merge(&[&other_proto_dir]);
+        let extracted_proto_dir = merged.extract_all(tmp_dir.path())?;
+
+        // Rerun paths cover both constituent trees, not just the primary one, even
+        // though `protos()` only reports the primary tree's files.
+        let rerun_paths = extracted_proto_dir.source_rerun_paths();
+        assert_eq!(rerun_paths.len(), 2 * extracted_proto_dir.protos().len());
+
+        Ok(())
+    }
+
     #[test]
     fn test_macro_include_proto_dir() -> Result<()> {
         const PROTO_DIR: ProtoDir = include_proto_dir!("proto");
@@ -0,0 +1,153 @@
+//! Generates a nested `mod.rs`-style include tree from the package names of
+//! prost/protoc-generated files, so `OUT_DIR/<mod_name>.rs` can be `include!`d once
+//! instead of hand-writing `pub mod` wrappers for every package.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::ProtoCompiler;
+
+/// Writes a single aggregator file to `out_dir/<mod_name>.rs` that nests every
+/// generated `.rs` file in `out_dir` under `pub mod` wrappers derived from its
+/// dot-separated package name.
+///
+/// The empty-package case (a file named `_.rs`) is included directly at the root
+/// instead of under a module, and intermediate modules shared by multiple files
+/// (e.g. `a.b.rs` and `a.c.rs` both nesting under `pub mod a`) are only emitted once.
+///
+/// # Errors
+///
+/// Returns an error if `out_dir` can't be read or the aggregator file can't be
+/// written.
+pub(crate) fn write_module_tree(out_dir: &Path, mod_name: &str) -> Result<()> {
+    let mut root = ModuleNode::default();
+
+    for entry in std::fs::read_dir(out_dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("rs") {
+            continue;
+        }
+        let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        if stem == mod_name {
+            continue;
+        }
+
+        let package: Vec<String> = if stem == "_" {
+            Vec::new()
+        } else {
+            stem.split('.').map(to_snake_case).collect()
+        };
+        root.insert(&package, &path);
+    }
+
+    let mut out = String::new();
+    root.write_children(&mut out, 0);
+    std::fs::write(out_dir.join(format!("{mod_name}.rs")), out)?;
+
+    Ok(())
+}
+
+/// One level of the generated module tree: the files `include!`d directly at this
+/// level, plus any nested `pub mod` children.
+#[derive(Default)]
+struct ModuleNode {
+    includes: Vec<PathBuf>,
+    children: BTreeMap<String, ModuleNode>,
+}
+
+impl ModuleNode {
+    fn insert(&mut self, package: &[String], file: &Path) {
+        match package.split_first() {
+            None => self.includes.push(file.to_path_buf()),
+            Some((head, rest)) => self
+                .children
+                .entry(head.clone())
+                .or_default()
+                .insert(rest, file),
+        }
+    }
+
+    fn write_children(&self, out: &mut String, depth: usize) {
+        let indent = "    ".repeat(depth);
+        for file in &self.includes {
+            out.push_str(&format!("{indent}include!({file:?});\n"));
+        }
+        for (name, child) in &self.children {
+            out.push_str(&format!("{indent}pub mod {name} {{\n"));
+            child.write_children(out, depth + 1);
+            out.push_str(&format!("{indent}}}\n"));
+        }
+    }
+}
+
+/// Snake-cases a single package segment (protoc package names are conventionally
+/// already lower-case, but this guards against CamelCase the way rules_rust's
+/// `snake_cased_package_name` does).
+fn to_snake_case(segment: &str) -> String {
+    let mut result = String::with_capacity(segment.len());
+    for (i, ch) in segment.chars().enumerate() {
+        if ch.is_uppercase() {
+            if i != 0 {
+                result.push('_');
+            }
+            result.extend(ch.to_lowercase());
+        } else {
+            result.push(ch);
+        }
+    }
+    result
+}
+
+impl<'a> ProtoCompiler<'a> {
+    /// Compiles this directory's protos (see [`Self::compile`]), then generates a
+    /// nested `mod.rs`-style aggregator at `OUT_DIR/<mod_name>.rs` covering every
+    /// generated file, so callers don't have to hand-write `pub mod` wrappers for
+    /// each package.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if compilation fails, `OUT_DIR` isn't set, or the
+    /// aggregator file can't be written.
+    pub fn write_module_tree(&mut self, mod_name: &str) -> Result<()> {
+        self.compile()?;
+        let out_dir = PathBuf::from(std::env::var("OUT_DIR")?);
+        write_module_tree(&out_dir, mod_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn nests_packages_and_dedups_shared_prefixes() -> Result<()> {
+        let tmp_dir = tempdir()?;
+        std::fs::write(tmp_dir.path().join("a.b.rs"), "pub struct B;")?;
+        std::fs::write(tmp_dir.path().join("a.c.rs"), "pub struct C;")?;
+        std::fs::write(tmp_dir.path().join("_.rs"), "pub struct Root;")?;
+
+        write_module_tree(tmp_dir.path(), "mod")?;
+        let generated = std::fs::read_to_string(tmp_dir.path().join("mod.rs"))?;
+
+        assert_eq!(generated.matches("pub mod a").count(), 1);
+        assert!(generated.contains("pub mod b"));
+        assert!(generated.contains("pub mod c"));
+        assert!(generated.contains("a.b.rs"));
+        assert!(generated.contains("a.c.rs"));
+        assert!(generated.contains("_.rs"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn snake_cases_camel_case_packages() {
+        assert_eq!(to_snake_case("fooBar"), "foo_bar");
+        assert_eq!(to_snake_case("FooBar"), "foo_bar");
+        assert_eq!(to_snake_case("foo"), "foo");
+    }
+}